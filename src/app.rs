@@ -1,24 +1,45 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use ansi_term::Color;
+use rustyline::completion::{Completer, Pair};
 use rustyline::error::ReadlineError;
 use rustyline::highlight::Highlighter;
-use rustyline::Editor;
-use rustyline_derive::{Completer, Helper, Hinter, Validator};
+use rustyline::{Context, Editor};
+use rustyline_derive::{Helper, Hinter, Validator};
 
 use crate::cmd::{parse_command, to_executor, CommandInput, CommandOutput};
 use crate::config::Config;
 use crate::error::Fallacy;
+use crate::fzf;
+use crate::plugin::{self, Plugin};
+use crate::registry;
 use crate::state::State;
 
 pub struct App {
     config: Config,
-    state: State,
+    state: Rc<RefCell<State>>,
     editor: Editor<PromptHighlighter>,
+    plugins: HashMap<String, Plugin>,
+    /// Flipped by the Ctrl-C handler; polled by executors so a long-running
+    /// command (e.g. a network metadata fetch) can bail out early. Cleared
+    /// before each top-level command so a prior interrupt can't poison the
+    /// next one.
+    cancel: Arc<AtomicBool>,
 }
 
-#[derive(Completer, Helper, Validator, Hinter)]
-struct PromptHighlighter;
+/// The known filter field prefixes that can appear as a command argument,
+/// e.g. `ls author:turing`.
+const FILTER_KEYS: &[&str] = &["author:", "title:", "year:", "venue:"];
+
+#[derive(Helper, Validator, Hinter)]
+struct PromptHighlighter {
+    state: Rc<RefCell<State>>,
+}
 
 impl Highlighter for PromptHighlighter {
     fn highlight_prompt<'b, 's: 'b, 'p: 'b>(
@@ -34,6 +55,106 @@ impl Highlighter for PromptHighlighter {
     }
 }
 
+impl Completer for PromptHighlighter {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, word) = current_word(line, pos);
+
+        // A pipe with no trailing space yet (e.g. `ls |`) lands in the same
+        // word as the `|` itself; strip it off so the rest of the logic
+        // only ever sees the command-name fragment being typed, and treat
+        // that as command position outright since a pipe always starts a
+        // new stage.
+        let (start, word, just_after_pipe) = match word.strip_prefix('|') {
+            Some(rest) => (start + 1, rest, true),
+            None => (start, word, false),
+        };
+
+        // The first word of a line, or the first word after a `|`, is a
+        // command name.
+        if just_after_pipe || is_command_position(&line[..start]) {
+            let candidates = complete_from(&registry::all_names(), word);
+            return Ok((start, candidates));
+        }
+
+        // Otherwise we're completing an argument. `author:`/`venue:`
+        // fragments complete against the distinct values already in the
+        // loaded state; anything else completes against the filter keys
+        // themselves.
+        for key in FILTER_KEYS {
+            if let Some(fragment) = word.strip_prefix(key) {
+                let values = match *key {
+                    "author:" => self.distinct(|p| p.authors.iter().cloned().collect()),
+                    "venue:" => self.distinct(|p| vec![p.venue.clone()]),
+                    _ => Vec::new(),
+                };
+                let candidates = complete_from(&values, fragment)
+                    .into_iter()
+                    .map(|pair| Pair {
+                        display: pair.display,
+                        replacement: format!("{}{}", key, pair.replacement),
+                    })
+                    .collect();
+                return Ok((start, candidates));
+            }
+        }
+
+        Ok((start, complete_from(FILTER_KEYS, word)))
+    }
+}
+
+impl PromptHighlighter {
+    fn distinct(&self, extract: impl Fn(&crate::state::Paper) -> Vec<String>) -> Vec<String> {
+        let mut values: Vec<String> = self
+            .state
+            .borrow()
+            .papers
+            .iter()
+            .flat_map(extract)
+            .collect();
+        values.sort();
+        values.dedup();
+        values
+    }
+}
+
+/// Find the word under the cursor and where it starts.
+fn current_word(line: &str, pos: usize) -> (usize, &str) {
+    let start = line[..pos]
+        .rfind(char::is_whitespace)
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    (start, &line[start..pos])
+}
+
+/// Whether the text preceding the current word puts us in command-name
+/// position, i.e. the line is empty so far, or the last non-whitespace
+/// token seen is a pipe.
+fn is_command_position(preceding: &str) -> bool {
+    match preceding.split_whitespace().last() {
+        None => true,
+        Some(token) => token == "|",
+    }
+}
+
+fn complete_from(candidates: &[impl AsRef<str>], word: &str) -> Vec<Pair> {
+    candidates
+        .iter()
+        .map(|c| c.as_ref())
+        .filter(|c| c.starts_with(word))
+        .map(|c| Pair {
+            display: c.to_owned(),
+            replacement: c.to_owned(),
+        })
+        .collect()
+}
+
 impl App {
     /// Initialize a new Reason app.
     pub fn init() -> Result<Self, Box<dyn std::error::Error>> {
@@ -55,8 +176,19 @@ impl App {
         // Check and fix the contents of the config.
         config.validate()?;
 
-        // Load metadata state.
-        let state = State::load(&config.storage.paper_metadata)?;
+        // Load metadata state. Shared with the completer so it can offer
+        // up-to-date author/venue values as papers are added or removed.
+        let state = Rc::new(RefCell::new(State::load(&config.storage.paper_metadata)?));
+
+        // Discover external command plugins, each launched once with
+        // `--reason-signature` to learn which command name(s) it provides.
+        let plugins = plugin::discover(&config.storage.plugin_dir);
+
+        // Install a Ctrl-C handler that just raises a flag; executors poll
+        // it themselves so we never interrupt a command mid-write.
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cancel_handler = cancel.clone();
+        ctrlc::set_handler(move || cancel_handler.store(true, Ordering::SeqCst))?;
 
         // Setup readline.
         let builder = rustyline::config::Builder::default();
@@ -65,7 +197,9 @@ impl App {
             .auto_add_history(true)
             .build();
         let mut editor = Editor::with_config(rlconfig);
-        editor.set_helper(Some(PromptHighlighter {}));
+        editor.set_helper(Some(PromptHighlighter {
+            state: state.clone(),
+        }));
 
         // Maybe create and load from command history file.
         let history_path = &config.storage.command_history;
@@ -83,11 +217,26 @@ impl App {
             );
         }
 
-        Ok(Self {
+        let mut app = Self {
             config,
             state,
             editor,
-        })
+            plugins,
+            cancel,
+        };
+
+        // Run the rc file, if any, through the same `source` machinery a
+        // user would invoke by hand, so it can pre-define aliases or
+        // default filters before the prompt shows up.
+        let rc_file = app.config.rc_file.clone();
+        if rc_file.exists() {
+            let command = format!("source {}", rc_file.display());
+            if let Err(e) = app.execute(&command) {
+                eprintln!("Failed to run rc file {:?}: {}", rc_file, e);
+            }
+        }
+
+        Ok(app)
     }
 
     /// The main command line loop.
@@ -101,6 +250,7 @@ impl App {
             match self.execute(args.join(" ").as_str()) {
                 Ok(msg) => print!("{}", msg),
                 Err(Fallacy::ExitReason) => {}
+                Err(Fallacy::Interrupted) => println!("aborted."),
                 Err(e) => println!("{}", e),
             };
             return Ok(());
@@ -113,6 +263,7 @@ impl App {
                 Ok(line) => match self.execute(&line) {
                     Ok(msg) => print!("{}", msg),
                     Err(Fallacy::ExitReason) => break,
+                    Err(Fallacy::Interrupted) => println!("aborted."),
                     Err(e) => println!("{}", e),
                 },
                 Err(ReadlineError::Interrupted) => continue,
@@ -133,7 +284,11 @@ impl App {
     /// - Save readline history
     pub fn terminate(&mut self) {
         // Save state to state file.
-        if let Err(e) = self.state.store(&self.config.storage.paper_metadata) {
+        if let Err(e) = self
+            .state
+            .borrow()
+            .store(&self.config.storage.paper_metadata)
+        {
             eprintln!("Error during teardown: {}", e);
         }
 
@@ -160,15 +315,33 @@ impl App {
     /// Runs a command entered by the user and returns a success or error message.
     /// The command may mutate the current state object.
     pub fn execute(&mut self, command: &str) -> Result<String, Fallacy> {
+        self.execute_at_depth(command, 0)
+    }
+
+    /// The same as [`execute`](Self::execute), but tracking how many nested
+    /// `source` calls got us here, so a script that sources itself can't
+    /// recurse forever.
+    fn execute_at_depth(&mut self, command: &str, depth: usize) -> Result<String, Fallacy> {
         // Parse the command.
         let commands = parse_command(command)?;
 
         // Run the command.
-        self.run_command(commands)
-            .map(|output| output.into_string(&self.state, &self.config))
+        self.run_command(commands, depth)
+            .map(|output| output.into_string(&self.state.borrow(), &self.config))
     }
 
-    fn run_command(&mut self, mut commands: Vec<Vec<String>>) -> Result<CommandOutput, Fallacy> {
+    fn run_command(
+        &mut self,
+        mut commands: Vec<Vec<String>>,
+        depth: usize,
+    ) -> Result<CommandOutput, Fallacy> {
+        // A prior interrupt must never carry over into the next top-level
+        // command. Lines fed in by `source` are not top-level, so a Ctrl-C
+        // partway through a script still aborts the rest of it.
+        if depth == 0 {
+            self.cancel.store(false, Ordering::SeqCst);
+        }
+
         // Probably impossible.
         if commands.is_empty() {
             return Ok(CommandOutput::None);
@@ -183,12 +356,12 @@ impl App {
                 if commands[0][0] == "#" {
                     return Ok(CommandOutput::None);
                 }
-                let executor = to_executor(commands[0][0].clone())?;
                 let input = CommandInput {
                     args: commands.remove(0),
                     papers: None,
+                    cancel: self.cancel.clone(),
                 };
-                return executor(input, &mut self.state, &self.config);
+                return self.dispatch(input, depth);
             }
         }
         // A chained command.
@@ -204,9 +377,67 @@ impl App {
             // A command is always given arguments. Commands that come after
             // the first one are given papers, but it's up to the command to
             // utilize it.
-            let executor = to_executor(command[0].clone())?;
-            let input = CommandInput::from_output(command, output);
-            output = executor(input, &mut self.state, &self.config)?;
+            let input = CommandInput::from_output(command, output, self.cancel.clone());
+            output = self.dispatch(input, depth)?;
+        }
+        Ok(output)
+    }
+
+    /// Run a single command, intercepting `source` (which needs access back
+    /// into `App::execute`), `help` and `fzf` (which need the command
+    /// registry and an interactive terminal respectively), and falling back
+    /// to an external plugin when the name doesn't match any built-in.
+    fn dispatch(&mut self, input: CommandInput, depth: usize) -> Result<CommandOutput, Fallacy> {
+        let name = input.args[0].clone();
+        if name == "source" {
+            let path = input.args.get(1).ok_or_else(|| {
+                Fallacy::InvalidCommand("source requires a path argument.".to_owned())
+            })?;
+            return self
+                .source(path, depth)
+                .map(CommandOutput::Message);
+        }
+        if name == "help" {
+            return Ok(CommandOutput::Message(registry::render_help(
+                &input.args[1..],
+            )));
+        }
+        if name == "fzf" {
+            let papers = input.papers.unwrap_or_default();
+            return fzf::pick(papers).map(CommandOutput::Papers);
+        }
+        match to_executor(name.clone()) {
+            Ok(executor) => executor(input, &mut self.state.borrow_mut(), &self.config),
+            Err(Fallacy::UnknownCommand(_)) => match self.plugins.get(&name) {
+                Some(plugin) => plugin::dispatch(plugin, &input),
+                None => Err(Fallacy::UnknownCommand(name)),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Read `path` line by line, feeding each through [`Self::execute_at_depth`]
+    /// so a script can contain chained commands just like an interactive
+    /// line. Comments (lines starting with `#`) are skipped by `run_command`
+    /// itself; any other `Fallacy` stops the script immediately.
+    fn source(&mut self, path: &str, depth: usize) -> Result<String, Fallacy> {
+        const MAX_SOURCE_DEPTH: usize = 16;
+        if depth >= MAX_SOURCE_DEPTH {
+            return Err(Fallacy::InvalidCommand(format!(
+                "source nested too deep (> {}), possible infinite recursion in {:?}",
+                MAX_SOURCE_DEPTH, path
+            )));
+        }
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| Fallacy::InvalidCommand(format!("failed to read {:?}: {}", path, e)))?;
+
+        let mut output = String::new();
+        for line in content.lines() {
+            if self.cancel.load(Ordering::SeqCst) {
+                return Err(Fallacy::Interrupted);
+            }
+            output.push_str(&self.execute_at_depth(line, depth + 1)?);
         }
         Ok(output)
     }
@@ -0,0 +1,264 @@
+use std::io::{self, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use crossterm::{cursor, execute, queue, style, terminal};
+
+use crate::error::Fallacy;
+use crate::state::Paper;
+
+/// How many rows of the candidate list to draw below the query line.
+const MAX_ROWS: usize = 15;
+
+/// Score a candidate against a query by walking the query's characters
+/// left-to-right and requiring them to appear in order in `candidate`.
+/// Matches at a word boundary or that continue a run of consecutive
+/// characters are worth more, so `"jsmi"` ranks "J. Smith" above
+/// "Jasmine Ito". Returns `None` if the query doesn't match at all.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let haystack: Vec<char> = candidate.chars().collect();
+    let needle: Vec<char> = query.chars().flat_map(|c| c.to_lowercase()).collect();
+
+    let mut score = 0i64;
+    let mut needle_pos = 0;
+    let mut prev_matched_at: Option<usize> = None;
+
+    for (i, &c) in haystack.iter().enumerate() {
+        if needle_pos == needle.len() {
+            break;
+        }
+        if c.to_ascii_lowercase() != needle[needle_pos] {
+            continue;
+        }
+
+        let at_word_boundary = i == 0 || !haystack[i - 1].is_alphanumeric();
+        let consecutive = prev_matched_at == Some(i.wrapping_sub(1));
+
+        score += 1;
+        if at_word_boundary {
+            score += 8;
+        }
+        if consecutive {
+            score += 4;
+        }
+
+        prev_matched_at = Some(i);
+        needle_pos += 1;
+    }
+
+    if needle_pos == needle.len() {
+        Some(score)
+    } else {
+        None
+    }
+}
+
+/// Score a paper against a query using its title and authors, keeping
+/// whichever field matches best.
+fn score_paper(query: &str, paper: &Paper) -> Option<i64> {
+    let title_score = fuzzy_score(query, &paper.title);
+    let author_score = paper
+        .authors
+        .iter()
+        .filter_map(|a| fuzzy_score(query, a))
+        .max();
+    title_score.into_iter().chain(author_score).max()
+}
+
+/// Rank `papers` against `query`, dropping anything that doesn't match and
+/// sorting the rest by descending score.
+fn filter_and_rank(papers: &[Paper], query: &str) -> Vec<usize> {
+    let mut ranked: Vec<(usize, i64)> = papers
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| score_paper(query, p).map(|score| (i, score)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1));
+    ranked.into_iter().map(|(i, _)| i).collect()
+}
+
+/// Open an interactive full-screen fuzzy picker over `papers`, returning
+/// the ones the user picked (empty if they cancelled with Esc).
+///
+/// Typing narrows the list via [`fuzzy_score`]; arrow keys move the
+/// highlight; Tab toggles multi-select on the highlighted row; Enter
+/// confirms (the highlighted row if nothing was Tab-selected); Esc cancels.
+pub fn pick(papers: Vec<Paper>) -> Result<Vec<Paper>, Fallacy> {
+    let mut stdout = io::stdout();
+    enable_raw_mode().map_err(|e| Fallacy::InvalidCommand(format!("failed to enter raw mode: {}", e)))?;
+    let result = run(&mut stdout, papers);
+    disable_raw_mode().ok();
+    result
+}
+
+fn run(stdout: &mut impl Write, papers: Vec<Paper>) -> Result<Vec<Paper>, Fallacy> {
+    let mut query = String::new();
+    let mut highlighted = 0usize;
+    let mut offset = 0usize;
+    let mut selected = std::collections::HashSet::new();
+
+    loop {
+        let matches = filter_and_rank(&papers, &query);
+        highlighted = highlighted.min(matches.len().saturating_sub(1));
+        offset = offset.clamp(
+            highlighted.saturating_sub(MAX_ROWS - 1),
+            highlighted,
+        );
+        render(stdout, &papers, &matches, &query, highlighted, offset, &selected)?;
+
+        let event = event::read().map_err(|e| Fallacy::InvalidCommand(format!("input error: {}", e)))?;
+        if let Event::Key(key) = event {
+            match (key.code, key.modifiers) {
+                (KeyCode::Esc, _) => return Ok(Vec::new()),
+                (KeyCode::Char('c'), KeyModifiers::CONTROL) => return Ok(Vec::new()),
+                (KeyCode::Enter, _) => {
+                    let chosen: Vec<Paper> = if selected.is_empty() {
+                        matches
+                            .get(highlighted)
+                            .map(|&i| papers[i].clone())
+                            .into_iter()
+                            .collect()
+                    } else {
+                        selected.iter().map(|&i| papers[i].clone()).collect()
+                    };
+                    return Ok(chosen);
+                }
+                (KeyCode::Tab, _) => {
+                    if let Some(&i) = matches.get(highlighted) {
+                        if !selected.insert(i) {
+                            selected.remove(&i);
+                        }
+                    }
+                }
+                (KeyCode::Up, _) => highlighted = highlighted.saturating_sub(1),
+                (KeyCode::Down, _) => {
+                    if highlighted + 1 < matches.len() {
+                        highlighted += 1;
+                    }
+                }
+                (KeyCode::Backspace, _) => {
+                    query.pop();
+                    highlighted = 0;
+                    offset = 0;
+                }
+                (KeyCode::Char(c), _) => {
+                    query.push(c);
+                    highlighted = 0;
+                    offset = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn render(
+    stdout: &mut impl Write,
+    papers: &[Paper],
+    matches: &[usize],
+    query: &str,
+    highlighted: usize,
+    offset: usize,
+    selected: &std::collections::HashSet<usize>,
+) -> Result<(), Fallacy> {
+    queue!(
+        stdout,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All),
+        style::Print(format!("> {}", query)),
+    )
+    .map_err(|e| Fallacy::InvalidCommand(format!("failed to render: {}", e)))?;
+
+    for (row, &i) in matches.iter().enumerate().skip(offset).take(MAX_ROWS) {
+        let marker = if selected.contains(&i) {
+            "*"
+        } else if row == highlighted {
+            ">"
+        } else {
+            " "
+        };
+        queue!(
+            stdout,
+            cursor::MoveTo(0, (row - offset + 1) as u16),
+            style::Print(format!("{} {}", marker, papers[i].title)),
+        )
+        .map_err(|e| Fallacy::InvalidCommand(format!("failed to render: {}", e)))?;
+    }
+
+    stdout
+        .flush()
+        .map_err(|e| Fallacy::InvalidCommand(format!("failed to render: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paper(title: &str, authors: &[&str]) -> Paper {
+        Paper {
+            title: title.to_owned(),
+            authors: authors.iter().map(|a| a.to_string()).collect(),
+            venue: String::new(),
+            year: 2020,
+        }
+    }
+
+    #[test]
+    fn fuzzy_score_requires_in_order_match() {
+        assert!(fuzzy_score("jsmi", "J. Smith").is_some());
+        assert!(fuzzy_score("mij", "J. Smith").is_none());
+    }
+
+    #[test]
+    fn fuzzy_score_rewards_word_boundary_matches() {
+        let boundary = fuzzy_score("s", "Smith").unwrap();
+        let mid_word = fuzzy_score("s", "Mosaic").unwrap();
+        assert!(boundary > mid_word);
+    }
+
+    #[test]
+    fn fuzzy_score_rewards_consecutive_runs() {
+        let consecutive = fuzzy_score("ab", "cabbage").unwrap();
+        let dispersed = fuzzy_score("ab", "cadber").unwrap();
+        assert!(consecutive > dispersed);
+    }
+
+    #[test]
+    fn filter_and_rank_drops_non_matches_and_sorts_by_score() {
+        let papers = vec![
+            paper("Attention Is All You Need", &["Vaswani"]),
+            paper("Deep Residual Learning", &["He", "Zhang"]),
+            paper("Strategic Planning and Search", &["Nash"]),
+            paper("A Survey of Transformers", &["Lin"]),
+        ];
+
+        let ranked = filter_and_rank(&papers, "trans");
+
+        // Only the titles containing t-r-a-n-s in order survive. "Transformers"
+        // matches as one run from a word boundary, so it outranks the
+        // scattered match in "Strategic Planning and Search".
+        assert_eq!(ranked, vec![3, 2]);
+    }
+
+    #[test]
+    fn filter_and_rank_matches_against_authors_too() {
+        let papers = vec![
+            paper("Attention Is All You Need", &["Vaswani"]),
+            paper("Deep Residual Learning", &["He", "Zhang"]),
+        ];
+
+        let ranked = filter_and_rank(&papers, "zhang");
+
+        assert_eq!(ranked, vec![1]);
+    }
+
+    #[test]
+    fn empty_query_matches_everything_in_original_order() {
+        let papers = vec![paper("A", &[]), paper("B", &[])];
+        assert_eq!(filter_and_rank(&papers, ""), vec![0, 1]);
+    }
+}
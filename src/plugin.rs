@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cmd::{CommandInput, CommandOutput};
+use crate::error::Fallacy;
+use crate::state::Paper;
+
+/// The flag a plugin executable is invoked with once, at startup, so it can
+/// report which commands it provides without us having to recompile reason.
+const SIGNATURE_FLAG: &str = "--reason-signature";
+
+/// How long a plugin gets to answer `--reason-signature` before we give up
+/// on it and move on, so one bad executable can't wedge `App::init`.
+const SIGNATURE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often we check `Child::try_wait`/the cancel flag while waiting on a
+/// plugin process.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// What a plugin reports about itself in response to `--reason-signature`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginDescriptor {
+    pub commands: Vec<String>,
+}
+
+/// The JSON-RPC request body sent to a plugin's stdin for a single command
+/// invocation. Mirrors `CommandInput` so plugins don't need to depend on
+/// reason's internal types.
+#[derive(Debug, Serialize)]
+struct PluginRequest<'a> {
+    args: &'a [String],
+    papers: &'a Option<Vec<Paper>>,
+}
+
+/// The JSON-RPC response body a plugin writes to stdout. Mirrors
+/// `CommandOutput`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PluginResponse {
+    Message(String),
+    Papers(Vec<Paper>),
+}
+
+impl From<PluginResponse> for CommandOutput {
+    fn from(response: PluginResponse) -> Self {
+        match response {
+            PluginResponse::Message(msg) => CommandOutput::Message(msg),
+            PluginResponse::Papers(papers) => CommandOutput::Papers(papers),
+        }
+    }
+}
+
+/// A plugin command discovered on disk, ready to be spawned on demand.
+pub struct Plugin {
+    pub path: PathBuf,
+}
+
+/// Scan `plugin_dir` for executables, launching each once with
+/// `--reason-signature` to learn the command name(s) it provides.
+///
+/// Plugins that fail to start, time out, or return an unparseable
+/// descriptor are skipped with a warning on stderr rather than aborting
+/// startup.
+pub fn discover(plugin_dir: &Path) -> HashMap<String, Plugin> {
+    let mut plugins = HashMap::new();
+
+    let entries = match std::fs::read_dir(plugin_dir) {
+        Ok(entries) => entries,
+        Err(_) => return plugins,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        match probe_signature(&path) {
+            Ok(descriptor) => {
+                for name in descriptor.commands {
+                    plugins.insert(name, Plugin { path: path.clone() });
+                }
+            }
+            Err(e) => eprintln!("Skipping plugin {:?}: {}", path, e),
+        }
+    }
+
+    plugins
+}
+
+/// Launch `path` with `--reason-signature` and read back its descriptor,
+/// bounded by [`SIGNATURE_TIMEOUT`] so a plugin that blocks (e.g. waiting
+/// on its own stdin) can't hang startup.
+fn probe_signature(path: &Path) -> Result<PluginDescriptor, String> {
+    let mut child = Command::new(path)
+        .arg(SIGNATURE_FLAG)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to launch: {}", e))?;
+
+    if wait_timeout(&mut child, SIGNATURE_TIMEOUT).is_none() {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(format!(
+            "timed out after {:?} responding to {}",
+            SIGNATURE_TIMEOUT, SIGNATURE_FLAG
+        ));
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("failed to read signature: {}", e))?;
+
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("invalid signature: {}", e))
+}
+
+/// Poll `child` until it exits or `timeout` elapses, returning its status
+/// in the former case and `None` in the latter (or if `try_wait` fails).
+/// `child` stays usable afterwards, so its output can still be collected.
+fn wait_timeout(child: &mut Child, timeout: Duration) -> Option<std::process::ExitStatus> {
+    let start = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) => {
+                if start.elapsed() >= timeout {
+                    return None;
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            Err(_) => return None,
+        }
+    }
+}
+
+/// Dispatch a command to a plugin, sending `input` as a JSON-RPC request on
+/// its stdin and reading a `CommandOutput` back from its stdout. Polls
+/// `input.cancel` while waiting on the plugin, killing it and returning
+/// `Fallacy::Interrupted` if the user hits Ctrl-C mid-request.
+pub fn dispatch(plugin: &Plugin, input: &CommandInput) -> Result<CommandOutput, Fallacy> {
+    let mut child = Command::new(&plugin.path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Fallacy::PluginError(format!("failed to spawn {:?}: {}", plugin.path, e)))?;
+
+    let request = PluginRequest {
+        args: &input.args,
+        papers: &input.papers,
+    };
+    let payload = serde_json::to_vec(&request)
+        .map_err(|e| Fallacy::PluginError(format!("failed to encode request: {}", e)))?;
+
+    // Write stdin and read stdout/stderr on their own threads, concurrently
+    // with the polling loop below. A plugin that streams back a large
+    // `Papers` response can fill a pipe buffer before we've drained the
+    // other one; reading/writing serially would deadlock the two of us
+    // against each other.
+    let mut stdin = child.stdin.take().expect("stdin was piped");
+    let writer = std::thread::spawn(move || stdin.write_all(&payload));
+
+    let mut stdout_pipe = child.stdout.take().expect("stdout was piped");
+    let (stdout_tx, stdout_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let result = stdout_pipe.read_to_end(&mut buf).map(|_| buf);
+        let _ = stdout_tx.send(result);
+    });
+
+    let mut stderr_pipe = child.stderr.take().expect("stderr was piped");
+    let (stderr_tx, stderr_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let result = stderr_pipe.read_to_end(&mut buf).map(|_| buf);
+        let _ = stderr_tx.send(result);
+    });
+
+    // Wait for the plugin to exit, polling the cancel flag so Ctrl-C can
+    // abort a plugin that's taking a while (e.g. fetching metadata).
+    loop {
+        if input.cancel.load(Ordering::SeqCst) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(Fallacy::Interrupted);
+        }
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                return Err(Fallacy::PluginError(format!(
+                    "failed to wait on {:?}: {}",
+                    plugin.path, e
+                )))
+            }
+        }
+    }
+    let status = child
+        .wait()
+        .map_err(|e| Fallacy::PluginError(format!("failed to wait on {:?}: {}", plugin.path, e)))?;
+
+    writer
+        .join()
+        .map_err(|_| Fallacy::PluginError(format!("stdin writer thread for {:?} panicked", plugin.path)))?
+        .map_err(|e| Fallacy::PluginError(format!("failed to write to {:?}: {}", plugin.path, e)))?;
+
+    let stdout = stdout_rx
+        .recv()
+        .map_err(|_| Fallacy::PluginError(format!("stdout reader thread for {:?} panicked", plugin.path)))?
+        .map_err(|e| Fallacy::PluginError(format!("failed to read from {:?}: {}", plugin.path, e)))?;
+    let stderr = stderr_rx
+        .recv()
+        .map_err(|_| Fallacy::PluginError(format!("stderr reader thread for {:?} panicked", plugin.path)))?
+        .map_err(|e| Fallacy::PluginError(format!("failed to read from {:?}: {}", plugin.path, e)))?;
+
+    if !status.success() {
+        return Err(Fallacy::PluginError(format!(
+            "{:?} exited with {}: {}",
+            plugin.path,
+            status,
+            String::from_utf8_lossy(&stderr)
+        )));
+    }
+
+    let response: PluginResponse = serde_json::from_slice(&stdout)
+        .map_err(|e| Fallacy::PluginError(format!("failed to parse response from {:?}: {}", plugin.path, e)))?;
+
+    Ok(response.into())
+}
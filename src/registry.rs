@@ -0,0 +1,183 @@
+//! The first-class command registry.
+//!
+//! Previously the set of valid command names only existed implicitly, as
+//! the match arms inside `cmd::to_executor`. That made it impossible for
+//! anything other than the dispatcher to know what commands existed. This
+//! module adds a `CommandSpec` table of help metadata (summary, usage,
+//! description, category) for commands worth documenting in detail, but the
+//! canonical list of *names* is still `cmd::command_names()` plus whatever
+//! `App::dispatch` intercepts before reaching it (`source`, `help`, `fzf`) —
+//! see [`all_names`]. Both `help` and the completer iterate that combined
+//! list, so neither loses a command just because it has no `CommandSpec`
+//! entry yet.
+
+/// Which part of reason a command belongs to, used to group the
+/// `help --tree` view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// Commands that read the library without changing it, e.g. `ls`.
+    Query,
+    /// Commands that change the library, e.g. `add`, `rm`.
+    Mutation,
+    /// Commands that move data in or out of reason, e.g. `source`.
+    Io,
+    /// Commands about reason itself, e.g. `help`.
+    Meta,
+}
+
+impl Category {
+    fn label(self) -> &'static str {
+        match self {
+            Category::Query => "query",
+            Category::Mutation => "mutation",
+            Category::Io => "io",
+            Category::Meta => "meta",
+        }
+    }
+}
+
+/// Metadata describing a single command.
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+    pub category: Category,
+}
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "ls",
+        summary: "List papers, optionally filtered",
+        usage: "ls [field:value...]",
+        description: "Lists papers in the library. With no arguments, lists \
+everything; otherwise each `field:value` argument (e.g. `author:turing`) \
+narrows the result.",
+        category: Category::Query,
+    },
+    CommandSpec {
+        name: "fzf",
+        summary: "Interactively fuzzy-pick papers from a pipeline",
+        usage: "fzf",
+        description: "Opens a full-screen fuzzy finder over the papers \
+piped into it, emitting only the ones picked so they can flow into the \
+next pipeline stage, e.g. `ls | fzf | open`.",
+        category: Category::Query,
+    },
+    CommandSpec {
+        name: "source",
+        summary: "Run a file of commands, one per line",
+        usage: "source <path>",
+        description: "Reads <path> line by line and feeds each line through \
+the same pipeline parsing as the interactive prompt, stopping at the first \
+error. Lines starting with `#` are treated as comments.",
+        category: Category::Io,
+    },
+    CommandSpec {
+        name: "help",
+        summary: "Show this help",
+        usage: "help [command|--tree]",
+        description: "With no arguments, prints a one-line summary of every command. \
+`help <command>` prints a single command's full usage. `help --tree` \
+groups every command by category.",
+        category: Category::Meta,
+    },
+    CommandSpec {
+        name: "quit",
+        summary: "Exit reason",
+        usage: "quit",
+        description: "Saves state and history, then exits.",
+        category: Category::Meta,
+    },
+];
+
+/// Commands that `App::dispatch` handles itself, before ever calling
+/// `cmd::to_executor`, and so wouldn't otherwise show up in completion or
+/// `help`.
+const INTERCEPTED: &[&str] = &["source", "help", "fzf"];
+
+pub fn find(name: &str) -> Option<&'static CommandSpec> {
+    COMMANDS.iter().find(|spec| spec.name == name)
+}
+
+/// The full set of command names reason understands: everything
+/// `cmd::to_executor` recognizes, plus the handful `App::dispatch`
+/// intercepts first. This is what the completer and `help` both iterate,
+/// so a command is never missing from one just because it lacks a
+/// `CommandSpec` entry.
+pub fn all_names() -> Vec<&'static str> {
+    let mut seen = std::collections::HashSet::new();
+    let mut names = Vec::new();
+    for name in crate::cmd::command_names().iter().chain(INTERCEPTED.iter()) {
+        if seen.insert(*name) {
+            names.push(*name);
+        }
+    }
+    names
+}
+
+/// `help` with no arguments: a one-line summary per command, falling back
+/// to a generic note for commands that have no `CommandSpec` yet.
+fn render_summary() -> String {
+    let names = all_names();
+    let width = names.iter().map(|n| n.len()).max().unwrap_or(0);
+    names
+        .iter()
+        .map(|n| {
+            let summary = find(n)
+                .map(|spec| spec.summary)
+                .unwrap_or("(no description available)");
+            format!("{:width$}  {}\n", n, summary, width = width)
+        })
+        .collect()
+}
+
+/// `help <command>`: the full usage and description for one command.
+fn render_usage(spec: &CommandSpec) -> String {
+    format!("{}\n\n    {}\n\n{}\n", spec.name, spec.usage, spec.description)
+}
+
+/// `help --tree`: every command grouped by category, indented underneath.
+/// Commands without a `CommandSpec` entry are listed last, ungrouped.
+fn render_tree() -> String {
+    let mut out = String::new();
+    for category in [
+        Category::Query,
+        Category::Mutation,
+        Category::Io,
+        Category::Meta,
+    ] {
+        out.push_str(category.label());
+        out.push('\n');
+        for spec in COMMANDS.iter().filter(|c| c.category == category) {
+            out.push_str(&format!("  {} - {}\n", spec.name, spec.summary));
+        }
+    }
+
+    let undocumented: Vec<&str> = all_names().into_iter().filter(|n| find(n).is_none()).collect();
+    if !undocumented.is_empty() {
+        out.push_str("undocumented\n");
+        for name in undocumented {
+            out.push_str(&format!("  {}\n", name));
+        }
+    }
+
+    out
+}
+
+/// Render the help text for `help`'s arguments (everything after the
+/// command name itself).
+pub fn render_help(args: &[String]) -> String {
+    match args {
+        [] => render_summary(),
+        [flag] if flag == "--tree" => render_tree(),
+        [name] => match find(name) {
+            Some(spec) => render_usage(spec),
+            None if all_names().contains(&name.as_str()) => {
+                format!("{}\n\nNo detailed help available yet.\n", name)
+            }
+            None => format!("No such command: {}\n", name),
+        },
+        _ => render_summary(),
+    }
+}